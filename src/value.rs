@@ -4,10 +4,100 @@ use utils::{escape_xml, format_datetime};
 
 use base64::encode;
 use iso8601::DateTime;
+use xml::reader::{EventReader, XmlEvent};
 
 use std::collections::BTreeMap;
-use std::io::{self, Write};
-use std::borrow::{Cow, ToOwned};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::borrow::{Borrow, Cow, ToOwned};
+use std::ops::{Deref, Index};
+use std::str;
+
+/// Backing storage for `Value::String`/`Value::Base64`.
+///
+/// Without the `bytes` cargo feature this is just `Cow<'a, [u8]>`. With it enabled, it can also
+/// hold a refcounted `bytes::Bytes` slice, so code parsing off an I/O buffer can hand out
+/// zero-copy views into the original allocation instead of copying into a fresh `Vec` or
+/// propagating a borrow lifetime through the whole `Value` tree.
+#[cfg(not(feature = "bytes"))]
+pub type ByteString<'a> = Cow<'a, [u8]>;
+
+/// Backing storage for `Value::String`/`Value::Base64`.
+///
+/// Without the `bytes` cargo feature this is just `Cow<'a, [u8]>`. With it enabled, it can also
+/// hold a refcounted `bytes::Bytes` slice, so code parsing off an I/O buffer can hand out
+/// zero-copy views into the original allocation instead of copying into a fresh `Vec` or
+/// propagating a borrow lifetime through the whole `Value` tree.
+#[cfg(feature = "bytes")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ByteString<'a> {
+    /// Data borrowed for, or copied into, this `Value`'s own lifetime.
+    Borrowed(Cow<'a, [u8]>),
+
+    /// A refcounted slice of a buffer shared with other owners.
+    Shared(::bytes::Bytes),
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> ByteString<'a> {
+    /// Detaches this `ByteString` from lifetime `'a`, cloning any borrowed data.
+    pub fn into_owned(self) -> ByteString<'static> {
+        match self {
+            ByteString::Borrowed(cow) => ByteString::Borrowed(Cow::Owned(cow.into_owned())),
+            ByteString::Shared(bytes) => ByteString::Shared(bytes),
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> Deref for ByteString<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            ByteString::Borrowed(ref cow) => cow,
+            ByteString::Shared(ref bytes) => bytes,
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> AsRef<[u8]> for ByteString<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> From<Cow<'a, [u8]>> for ByteString<'a> {
+    fn from(other: Cow<'a, [u8]>) -> Self {
+        ByteString::Borrowed(other)
+    }
+}
+
+/// Lets a `Value::String`/`Value::Base64` be constructed directly from a refcounted `bytes::Bytes`
+/// slice without copying it, e.g. when handing out a zero-copy view into a network buffer.
+#[cfg(feature = "bytes")]
+impl<'a> From<::bytes::Bytes> for ByteString<'a> {
+    fn from(other: ::bytes::Bytes) -> Self {
+        ByteString::Shared(other)
+    }
+}
+
+/// Detaches a `ByteString` from lifetime `'a`, cloning any borrowed data.
+///
+/// This is just `ByteString::into_owned` without the `bytes` feature, where `ByteString` is a
+/// plain `Cow` alias rather than an inherent-method-bearing type.
+#[cfg(not(feature = "bytes"))]
+fn byte_string_into_owned(s: ByteString) -> ByteString<'static> {
+    Cow::<[u8]>::Owned(s.into_owned())
+}
+
+/// Detaches a `ByteString` from lifetime `'a`, cloning any borrowed data.
+#[cfg(feature = "bytes")]
+fn byte_string_into_owned(s: ByteString) -> ByteString<'static> {
+    s.into_owned()
+}
 
 /// The possible XML-RPC values.
 ///
@@ -35,7 +125,7 @@ pub enum Value<'a> {
     /// is necessary.
     ///
     /// [spec]: https://web.archive.org/web/20050913062502/http://www.xmlrpc.com/spec
-    String(Cow<'a, [u8]>),
+    String(ByteString<'a>),
 
     /// `<double>`
     Double(f64),
@@ -44,7 +134,7 @@ pub enum Value<'a> {
     DateTime(DateTime),
 
     /// `<base64>`, base64-encoded binary data.
-    Base64(Cow<'a, [u8]>),
+    Base64(ByteString<'a>),
 
     /// `<struct>`, a mapping of named values.
     ///
@@ -74,13 +164,51 @@ pub enum Value<'a> {
     Nil,
 }
 
+#[repr(transparent)]
 struct Slice<T>([T]);
 
-impl<T> ToOwned for Slice<T> {
+impl<T: fmt::Debug> fmt::Debug for Slice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Slice<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Clone> ToOwned for Slice<T> {
     type Owned = Vec<T>;
 
-    fn to_owned(self) -> Self::Owned {
-        Vec::from(&self.0)
+    fn to_owned(&self) -> Self::Owned {
+        self.0.to_vec()
+    }
+}
+
+// `Cow<'a, Slice<T>>` requires `Vec<T>: Borrow<Slice<T>>`; since `Slice<T>` is a
+// `#[repr(transparent)]` wrapper around `[T]`, borrowing a `Vec<T>` as one is just a pointer cast.
+impl<T> Borrow<Slice<T>> for Vec<T> {
+    fn borrow(&self) -> &Slice<T> {
+        unsafe { &*(self.as_slice() as *const [T] as *const Slice<T>) }
+    }
+}
+
+impl<T> Deref for Slice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Slice<T> {
+    type Item = &'a T;
+    type IntoIter = ::std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
     }
 }
 
@@ -100,7 +228,14 @@ impl<'a> Value<'a> {
                 try!(writeln!(fmt, "<boolean>{}</boolean>", if b { "1" } else { "0" }));
             }
             Value::String(ref s) => {
-                try!(writeln!(fmt, "<string>{}</string>", escape_xml(s)));
+                let text = match str::from_utf8(s) {
+                    Ok(text) => text,
+                    Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "string value is not valid UTF-8, use Value::Base64 for binary data")),
+                };
+                if let Some(c) = text.chars().find(|c| !is_valid_xml_char(*c)) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("string value contains {:?}, which cannot be represented in XML", c)));
+                }
+                try!(writeln!(fmt, "<string>{}</string>", escape_xml(text.as_bytes())));
             }
             Value::Double(d) => {
                 try!(writeln!(fmt, "<double>{}</double>", d));
@@ -113,7 +248,7 @@ impl<'a> Value<'a> {
             }
             Value::Struct(ref map) => {
                 try!(writeln!(fmt, "<struct>"));
-                for (ref name, ref value) in map {
+                for (ref name, ref value) in map.iter() {
                     try!(writeln!(fmt, "<member>"));
                     try!(writeln!(fmt, "<name>{}</name>", escape_xml(name)));
                     try!(value.format(fmt));
@@ -124,7 +259,7 @@ impl<'a> Value<'a> {
             Value::Array(ref array) => {
                 try!(writeln!(fmt, "<array>"));
                 try!(writeln!(fmt, "<data>"));
-                for value in array {
+                for value in array.iter() {
                     try!(value.format(fmt));
                 }
                 try!(writeln!(fmt, "</data>"));
@@ -138,6 +273,138 @@ impl<'a> Value<'a> {
         try!(writeln!(fmt, "</value>"));
         Ok(())
     }
+
+    /// Returns the inner value as an `i32`, if it is an `Int`.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            Value::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as an `i64`, if it is an `Int` or `Int64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(i) => Some(i as i64),
+            Value::Int64(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a `bool`, if it is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as an `f64`, if it is a `Double`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Double(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a `&str`, if it is a `String` whose bytes are valid UTF-8.
+    ///
+    /// Since `Value::String` doesn't guarantee valid UTF-8, this returns `None` both when the
+    /// variant doesn't match and when the bytes aren't a valid string.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => str::from_utf8(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a byte slice, if it is a `String` or `Base64`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            Value::String(ref s) | Value::Base64(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a `DateTime`, if it is a `DateTime`.
+    pub fn as_datetime(&self) -> Option<DateTime> {
+        match *self {
+            Value::DateTime(date_time) => Some(date_time),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a slice of `Value`s, if it is an `Array`.
+    pub fn as_array(&self) -> Option<&[Value<'a>]> {
+        match *self {
+            Value::Array(ref array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner value as a slice of struct members, if it is a `Struct`.
+    pub fn as_struct(&self) -> Option<&[(Cow<'a, str>, Value<'a>)]> {
+        match *self {
+            Value::Struct(ref members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Looks up a member of a `Struct` by name, returning the first match.
+    ///
+    /// Per the [spec][dup], struct member names aren't guaranteed unique; this returns the first
+    /// one found rather than panicking or picking arbitrarily.
+    ///
+    /// [dup]: http://xml-rpc.yahoogroups.narkive.com/Br9xMUtQ/duplicate-struct-member-names-allowed
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.as_struct()
+            .and_then(|members| members.iter().find(|(name, _)| name == key))
+            .map(|(_, value)| value)
+    }
+
+    /// Detaches this `Value` from lifetime `'a`, cloning any borrowed data.
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Int(i) => Value::Int(i),
+            Value::Int64(i) => Value::Int64(i),
+            Value::Bool(b) => Value::Bool(b),
+            Value::String(s) => Value::String(byte_string_into_owned(s)),
+            Value::Double(d) => Value::Double(d),
+            Value::DateTime(date_time) => Value::DateTime(date_time),
+            Value::Base64(s) => Value::Base64(byte_string_into_owned(s)),
+            Value::Struct(members) => Value::Struct(Cow::Owned(
+                members
+                    .into_owned()
+                    .into_iter()
+                    .map(|(name, value)| (Cow::Owned(name.into_owned()), value.into_owned()))
+                    .collect(),
+            )),
+            Value::Array(elements) => {
+                Value::Array(Cow::Owned(elements.into_owned().into_iter().map(Value::into_owned).collect()))
+            }
+            Value::Nil => Value::Nil,
+        }
+    }
+}
+
+/// Indexes into a `Struct` by member name, panicking if the `Value` isn't a `Struct` or has no
+/// member with that name.
+impl<'a, 'b> Index<&'b str> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, key: &'b str) -> &Value<'a> {
+        self.get(key).expect("no struct member with that name")
+    }
+}
+
+/// Indexes into an `Array` by position, panicking if the `Value` isn't an `Array` or the index is
+/// out of bounds.
+impl<'a> Index<usize> for Value<'a> {
+    type Output = Value<'a>;
+
+    fn index(&self, index: usize) -> &Value<'a> {
+        &self.as_array().expect("not an array")[index]
+    }
 }
 
 impl<'a> From<i32> for Value<'a> {
@@ -154,13 +421,13 @@ impl<'a> From<bool> for Value<'a> {
 
 impl<'a> From<String> for Value<'a> {
     fn from(other: String) -> Self {
-        Value::String(other)
+        Value::String(Cow::<[u8]>::Owned(other.into_bytes()).into())
     }
 }
 
 impl<'a> From<&'a str> for Value<'a> {
     fn from(other: &'a str) -> Self {
-        Value::String(Cow::from(other.as_slice()))
+        Value::String(Cow::Borrowed(other.as_bytes()).into())
     }
 }
 
@@ -176,6 +443,835 @@ impl<'a> From<DateTime> for Value<'a> {
     }
 }
 
+impl<'a> From<i64> for Value<'a> {
+    fn from(other: i64) -> Self {
+        Value::Int64(other)
+    }
+}
+
+impl<'a> From<f32> for Value<'a> {
+    fn from(other: f32) -> Self {
+        Value::Double(other as f64)
+    }
+}
+
+impl<'a> From<Vec<Value<'a>>> for Value<'a> {
+    fn from(other: Vec<Value<'a>>) -> Self {
+        Value::Array(Cow::Owned(other))
+    }
+}
+
+impl<'a> From<BTreeMap<String, Value<'a>>> for Value<'a> {
+    fn from(other: BTreeMap<String, Value<'a>>) -> Self {
+        Value::Struct(Cow::Owned(other.into_iter().map(|(k, v)| (Cow::Owned(k), v)).collect()))
+    }
+}
+
+/// `Value::String`, treating the bytes as a Rust byte string rather than base64-encoded binary
+/// data.
+///
+/// Since a `<string>` element can only hold valid, XML-safe character data, prefer
+/// [`Value::from_bytes`] when the content might not be text: it automatically falls back to
+/// `Value::Base64` for bytes that aren't safely representable as XML text.
+impl<'a> From<Vec<u8>> for Value<'a> {
+    fn from(other: Vec<u8>) -> Self {
+        Value::String(Cow::<[u8]>::Owned(other).into())
+    }
+}
+
+/// See the `From<Vec<u8>>` impl; the same caveat about `<string>` vs. `<base64>` applies here.
+impl<'a> From<&'a [u8]> for Value<'a> {
+    fn from(other: &'a [u8]) -> Self {
+        Value::String(Cow::Borrowed(other).into())
+    }
+}
+
+/// Returns whether `c` is allowed to appear as XML 1.0 character data, per the
+/// [`Char` production](https://www.w3.org/TR/xml/#charsets).
+fn is_valid_xml_char(c: char) -> bool {
+    match c as u32 {
+        0x9 | 0xA | 0xD => true,
+        0x20..=0xD7FF => true,
+        0xE000..=0xFFFD => true,
+        0x10000..=0x10FFFF => true,
+        _ => false,
+    }
+}
+
+impl<'a> Value<'a> {
+    /// Builds a `Value` from raw bytes, the way you usually want: `Value::String` when the bytes
+    /// are valid, XML-safe text, or `Value::Base64` otherwise.
+    ///
+    /// Use the `From<Vec<u8>>`/`From<&[u8]>` impls directly if you need a `Value::String` whatever
+    /// the content; `format` then fails for bytes that aren't XML-safe text.
+    pub fn from_bytes<B: Into<Cow<'a, [u8]>>>(data: B) -> Value<'a> {
+        let data = data.into();
+
+        let is_xml_safe_text = match str::from_utf8(&data) {
+            Ok(text) => text.chars().all(is_valid_xml_char),
+            Err(_) => false,
+        };
+
+        if is_xml_safe_text {
+            Value::String(data.into())
+        } else {
+            Value::Base64(data.into())
+        }
+    }
+}
+
+/// An error encountered while parsing a `<value>` document.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The underlying XML document was malformed.
+    Xml(::xml::reader::Error),
+
+    /// The document ended before a complete `<value>` was read.
+    UnexpectedEof,
+
+    /// An element or piece of text was found where it didn't belong, e.g. a `<member>` outside
+    /// of a `<struct>`.
+    UnexpectedEvent(String),
+
+    /// An `<i4>`/`<int>`/`<i8>` didn't contain a valid integer.
+    InvalidInt(String),
+
+    /// A `<double>` didn't contain a valid floating point number.
+    InvalidDouble(String),
+
+    /// A `<base64>` didn't contain valid base64.
+    InvalidBase64(String),
+
+    /// A `<dateTime.iso8601>` didn't contain a valid ISO 8601 date/time.
+    InvalidDateTime(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Xml(ref err) => write!(f, "malformed XML: {}", err),
+            ParseError::UnexpectedEof => write!(f, "unexpected end of document"),
+            ParseError::UnexpectedEvent(ref what) => write!(f, "unexpected {}", what),
+            ParseError::InvalidInt(ref text) => write!(f, "invalid integer: {}", text),
+            ParseError::InvalidDouble(ref text) => write!(f, "invalid double: {}", text),
+            ParseError::InvalidBase64(ref text) => write!(f, "invalid base64: {}", text),
+            ParseError::InvalidDateTime(ref text) => write!(f, "invalid date/time: {}", text),
+        }
+    }
+}
+
+impl ::std::error::Error for ParseError {
+    fn description(&self) -> &str {
+        "error parsing an XML-RPC value"
+    }
+}
+
+impl From<::xml::reader::Error> for ParseError {
+    fn from(other: ::xml::reader::Error) -> Self {
+        ParseError::Xml(other)
+    }
+}
+
+impl<'a> Value<'a> {
+    /// Parses a `<value>...</value>` document into an owned `Value`.
+    pub fn parse<R: Read>(reader: R) -> Result<Value<'static>, ParseError> {
+        let mut events = EventReader::new(reader);
+        expect_value_start(&mut events)?;
+        parse_value_body(&mut events)
+    }
+
+    /// Parses a `<value>...</value>` document from a string into an owned `Value`.
+    pub fn parse_str(s: &str) -> Result<Value<'static>, ParseError> {
+        Value::parse(s.as_bytes())
+    }
+
+    /// Builds a `Value::String` from a refcounted `bytes::Bytes` slice without copying it.
+    #[cfg(feature = "bytes")]
+    pub fn bytes_from(data: ::bytes::Bytes) -> Value<'static> {
+        Value::String(ByteString::from(data))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> From<::bytes::Bytes> for Value<'a> {
+    fn from(other: ::bytes::Bytes) -> Self {
+        Value::String(ByteString::from(other))
+    }
+}
+
+/// Advances past `StartDocument`/whitespace/comments until the opening `<value>` tag.
+fn expect_value_start<R: Read>(events: &mut EventReader<R>) -> Result<(), ParseError> {
+    loop {
+        match events.next()? {
+            XmlEvent::StartDocument { .. } | XmlEvent::Whitespace(_) | XmlEvent::Comment(_) => continue,
+            XmlEvent::StartElement { ref name, .. } if name.local_name == "value" => return Ok(()),
+            XmlEvent::EndDocument => return Err(ParseError::UnexpectedEof),
+            other => return Err(ParseError::UnexpectedEvent(format!("{:?}", other))),
+        }
+    }
+}
+
+/// Reads the next non-whitespace event, expecting it to be the start tag of `name`.
+fn expect_start<R: Read>(events: &mut EventReader<R>, name: &str) -> Result<(), ParseError> {
+    match next_significant(events)? {
+        XmlEvent::StartElement { name: found, .. } if found.local_name == name => Ok(()),
+        other => Err(ParseError::UnexpectedEvent(format!("expected <{}>, got {:?}", name, other))),
+    }
+}
+
+/// Reads the next non-whitespace event, expecting it to be the end tag of `name`.
+fn expect_end<R: Read>(events: &mut EventReader<R>, name: &str) -> Result<(), ParseError> {
+    match next_significant(events)? {
+        XmlEvent::EndElement { name: found } if found.local_name == name => Ok(()),
+        other => Err(ParseError::UnexpectedEvent(format!("expected </{}>, got {:?}", name, other))),
+    }
+}
+
+/// Reads the next event, skipping whitespace-only text and comments.
+fn next_significant<R: Read>(events: &mut EventReader<R>) -> Result<XmlEvent, ParseError> {
+    loop {
+        match events.next()? {
+            XmlEvent::Whitespace(_) | XmlEvent::Comment(_) => continue,
+            XmlEvent::EndDocument => return Err(ParseError::UnexpectedEof),
+            other => return Ok(other),
+        }
+    }
+}
+
+/// Reads the character data of an element and consumes its end tag.
+fn read_text_content<R: Read>(events: &mut EventReader<R>, name: &str) -> Result<String, ParseError> {
+    match next_significant(events)? {
+        XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+            expect_end(events, name)?;
+            Ok(text)
+        }
+        XmlEvent::EndElement { name: found } if found.local_name == name => Ok(String::new()),
+        other => Err(ParseError::UnexpectedEvent(format!("expected text content of <{}>, got {:?}", name, other))),
+    }
+}
+
+/// Parses the body of a `<value>` (everything after the opening tag) and consumes its closing
+/// `</value>`.
+fn parse_value_body<R: Read>(events: &mut EventReader<R>) -> Result<Value<'static>, ParseError> {
+    match next_significant(events)? {
+        XmlEvent::EndElement { ref name } if name.local_name == "value" => Ok(Value::from(String::new())),
+        XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+            expect_end(events, "value")?;
+            Ok(Value::from(text))
+        }
+        XmlEvent::StartElement { ref name, .. } => {
+            let value = match &*name.local_name {
+                "i4" | "int" => {
+                    let text = read_text_content(events, &name.local_name)?;
+                    Value::Int(text.parse().map_err(|_| ParseError::InvalidInt(text.clone()))?)
+                }
+                "i8" => Value::Int64(read_text_content(events, "i8")?.parse().map_err(|e: ::std::num::ParseIntError| ParseError::InvalidInt(e.to_string()))?),
+                "boolean" => Value::Bool(read_text_content(events, "boolean")? == "1"),
+                "string" => Value::from(read_text_content(events, "string")?),
+                "double" => Value::Double(read_text_content(events, "double")?.parse().map_err(|e: ::std::num::ParseFloatError| ParseError::InvalidDouble(e.to_string()))?),
+                "dateTime.iso8601" => {
+                    let text = read_text_content(events, "dateTime.iso8601")?;
+                    Value::DateTime(::iso8601::datetime(&text).map_err(ParseError::InvalidDateTime)?)
+                }
+                "base64" => {
+                    let text = read_text_content(events, "base64")?;
+                    let data = ::base64::decode(&text).map_err(|e| ParseError::InvalidBase64(e.to_string()))?;
+                    Value::Base64(Cow::<[u8]>::Owned(data).into())
+                }
+                "struct" => {
+                    let mut members = Vec::new();
+                    loop {
+                        match next_significant(events)? {
+                            XmlEvent::EndElement { ref name } if name.local_name == "struct" => break,
+                            XmlEvent::StartElement { ref name, .. } if name.local_name == "member" => {
+                                expect_start(events, "name")?;
+                                let key = read_text_content(events, "name")?;
+                                expect_start(events, "value")?;
+                                let value = parse_value_body(events)?;
+                                expect_end(events, "member")?;
+                                members.push((Cow::Owned(key), value));
+                            }
+                            other => return Err(ParseError::UnexpectedEvent(format!("expected <member> or </struct>, got {:?}", other))),
+                        }
+                    }
+                    Value::Struct(Cow::Owned(members))
+                }
+                "array" => {
+                    expect_start(events, "data")?;
+                    let mut elements = Vec::new();
+                    loop {
+                        match next_significant(events)? {
+                            XmlEvent::EndElement { ref name } if name.local_name == "data" => break,
+                            XmlEvent::StartElement { ref name, .. } if name.local_name == "value" => {
+                                elements.push(parse_value_body(events)?);
+                            }
+                            other => return Err(ParseError::UnexpectedEvent(format!("expected <value> or </data>, got {:?}", other))),
+                        }
+                    }
+                    expect_end(events, "array")?;
+                    Value::Array(Cow::Owned(elements))
+                }
+                "nil" => {
+                    match next_significant(events)? {
+                        XmlEvent::EndElement { ref name } if name.local_name == "nil" => {}
+                        other => return Err(ParseError::UnexpectedEvent(format!("expected />, got {:?}", other))),
+                    }
+                    Value::Nil
+                }
+                other => return Err(ParseError::UnexpectedEvent(format!("unknown value type <{}>", other))),
+            };
+
+            expect_end(events, "value")?;
+            Ok(value)
+        }
+        other => Err(ParseError::UnexpectedEvent(format!("{:?}", other))),
+    }
+}
+
+/// Conversion between `Value` and arbitrary Rust types via `serde`.
+///
+/// Gated behind the `serde` cargo feature so the rest of the crate doesn't pull in `serde` when
+/// it isn't wanted.
+#[cfg(feature = "serde")]
+pub mod serde_support {
+    use super::{ByteString, Value};
+
+    use serde::{de, ser};
+    use serde::de::{DeserializeOwned, Visitor};
+    use serde::ser::{Serialize, SerializeMap, SerializeStruct};
+
+    use std::borrow::Cow;
+    use std::fmt;
+
+    /// The name used for the newtype wrapper that marks a value as a `Value::DateTime` rather
+    /// than a plain string.
+    ///
+    /// Since `iso8601::DateTime` doesn't implement `Serialize`/`Deserialize` itself, wrap it in a
+    /// newtype struct with this name (and an inner RFC 3339-ish `String`) to round-trip through
+    /// `to_value`/`from_value` without losing the `DateTime` variant:
+    ///
+    /// ```ignore
+    /// struct XmlRpcDateTime(String);
+    ///
+    /// impl Serialize for XmlRpcDateTime {
+    ///     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    ///         s.serialize_newtype_struct(DATETIME_NEWTYPE_NAME, &self.0)
+    ///     }
+    /// }
+    /// ```
+    pub const DATETIME_NEWTYPE_NAME: &str = "$__xml_rpc_rs__DateTime";
+
+    /// Errors that can occur while converting between `Value` and an arbitrary Rust type.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Error {
+        /// A custom error message, either ours or from the `Serialize`/`Deserialize` impl being
+        /// driven.
+        Message(String),
+
+        /// A `Value` didn't have the shape the target type expected.
+        UnexpectedValue {
+            /// A short description of what was expected, e.g. `"a struct"`.
+            expected: &'static str,
+        },
+
+        /// A `Value::String` contained bytes that are not valid UTF-8.
+        NotUtf8,
+
+        /// An integer didn't fit into the requested Rust integer type.
+        IntegerOutOfRange,
+
+        /// A `DATETIME_NEWTYPE_NAME` value didn't contain a valid ISO 8601 date/time string.
+        InvalidDateTime(String),
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Error::Message(ref msg) => write!(f, "{}", msg),
+                Error::UnexpectedValue { expected } => write!(f, "expected {}", expected),
+                Error::NotUtf8 => write!(f, "string is not valid UTF-8"),
+                Error::IntegerOutOfRange => write!(f, "integer out of range"),
+                Error::InvalidDateTime(ref msg) => write!(f, "invalid date/time: {}", msg),
+            }
+        }
+    }
+
+    impl ::std::error::Error for Error {
+        fn description(&self) -> &str {
+            "error converting between Value and a Rust type"
+        }
+    }
+
+    impl ser::Error for Error {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            Error::Message(msg.to_string())
+        }
+    }
+
+    impl de::Error for Error {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            Error::Message(msg.to_string())
+        }
+    }
+
+    /// Converts any `T: Serialize` into a `Value`.
+    pub fn to_value<T: Serialize>(value: T) -> Result<Value<'static>, Error> {
+        value.serialize(Serializer)
+    }
+
+    /// Converts a `Value` into any `T: DeserializeOwned`.
+    pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+        T::deserialize(Deserializer { value: Value::into_owned(value) })
+    }
+
+    /// A `serde::Serializer` that turns a `T: Serialize` into a `Value<'static>`.
+    pub struct Serializer;
+
+    impl ser::Serializer for Serializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        type SerializeSeq = ArraySerializer;
+        type SerializeTuple = ArraySerializer;
+        type SerializeTupleStruct = ArraySerializer;
+        type SerializeTupleVariant = ArraySerializer;
+        type SerializeMap = MapSerializer;
+        type SerializeStruct = StructSerializer;
+        type SerializeStructVariant = StructSerializer;
+
+        fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Bool(v))
+        }
+
+        fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+            self.serialize_i32(v as i32)
+        }
+
+        fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+            self.serialize_i32(v as i32)
+        }
+
+        fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Int(v))
+        }
+
+        fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Int64(v))
+        }
+
+        fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+            self.serialize_i32(v as i32)
+        }
+
+        fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+            self.serialize_i32(v as i32)
+        }
+
+        fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+            self.serialize_i64(v as i64)
+        }
+
+        fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+            if v > i64::max_value() as u64 {
+                return Err(Error::IntegerOutOfRange);
+            }
+            self.serialize_i64(v as i64)
+        }
+
+        fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+            self.serialize_f64(v as f64)
+        }
+
+        fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Double(v))
+        }
+
+        fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+            self.serialize_str(&v.to_string())
+        }
+
+        fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::from(v.to_string()))
+        }
+
+        fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Base64(Cow::<[u8]>::Owned(v.to_vec()).into()))
+        }
+
+        fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Nil)
+        }
+
+        fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+            value.serialize(self)
+        }
+
+        fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Nil)
+        }
+
+        fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+            self.serialize_unit()
+        }
+
+        fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+            self.serialize_str(variant)
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+            if name == DATETIME_NEWTYPE_NAME {
+                let inner = value.serialize(Serializer)?;
+                let text = match inner {
+                    Value::String(s) => String::from_utf8(s.to_vec()).map_err(|_| Error::NotUtf8)?,
+                    _ => return Err(Error::UnexpectedValue { expected: "a date/time string" }),
+                };
+                let date_time = ::iso8601::datetime(&text).map_err(Error::InvalidDateTime)?;
+                return Ok(Value::DateTime(date_time));
+            }
+
+            value.serialize(self)
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+            let inner = value.serialize(Serializer)?;
+            Ok(Value::Struct(Cow::Owned(vec![(Cow::Borrowed(variant), inner)])))
+        }
+
+        fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+            Ok(ArraySerializer { elements: Vec::with_capacity(len.unwrap_or(0)) })
+        }
+
+        fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+            self.serialize_seq(Some(len))
+        }
+
+        fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
+            self.serialize_seq(Some(len))
+        }
+
+        fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
+            self.serialize_seq(Some(len))
+        }
+
+        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+            Ok(MapSerializer { members: Vec::new(), next_key: None })
+        }
+
+        fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+            Ok(StructSerializer { members: Vec::with_capacity(len) })
+        }
+
+        fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+            let _ = variant;
+            Ok(StructSerializer { members: Vec::with_capacity(len) })
+        }
+    }
+
+    /// Serializes a sequence/tuple into a `Value::Array`.
+    pub struct ArraySerializer {
+        elements: Vec<Value<'static>>,
+    }
+
+    impl ser::SerializeSeq for ArraySerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+            self.elements.push(value.serialize(Serializer)?);
+            Ok(())
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Array(Cow::Owned(self.elements)))
+        }
+    }
+
+    impl ser::SerializeTuple for ArraySerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    impl ser::SerializeTupleStruct for ArraySerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    impl ser::SerializeTupleVariant for ArraySerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    /// Serializes a map into a `Value::Struct`, requiring string-like keys.
+    pub struct MapSerializer {
+        members: Vec<(Cow<'static, str>, Value<'static>)>,
+        next_key: Option<Cow<'static, str>>,
+    }
+
+    impl SerializeMap for MapSerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+            let key = match key.serialize(Serializer)? {
+                Value::String(s) => String::from_utf8(s.to_vec()).map_err(|_| Error::NotUtf8)?,
+                _ => return Err(Error::UnexpectedValue { expected: "a string struct key" }),
+            };
+            self.next_key = Some(Cow::Owned(key));
+            Ok(())
+        }
+
+        fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+            let key = self.next_key.take().expect("serialize_value called before serialize_key");
+            self.members.push((key, value.serialize(Serializer)?));
+            Ok(())
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Struct(Cow::Owned(self.members)))
+        }
+    }
+
+    /// Serializes a struct into a `Value::Struct`.
+    pub struct StructSerializer {
+        members: Vec<(Cow<'static, str>, Value<'static>)>,
+    }
+
+    impl SerializeStruct for StructSerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+            self.members.push((Cow::Borrowed(key), value.serialize(Serializer)?));
+            Ok(())
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Struct(Cow::Owned(self.members)))
+        }
+    }
+
+    impl ser::SerializeStructVariant for StructSerializer {
+        type Ok = Value<'static>;
+        type Error = Error;
+
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+            SerializeStruct::serialize_field(self, key, value)
+        }
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            SerializeStruct::end(self)
+        }
+    }
+
+    /// A `serde::Deserializer` that walks a `Value` and drives a `Visitor`.
+    pub struct Deserializer {
+        value: Value<'static>,
+    }
+
+    impl Deserializer {
+        fn from_str(s: ByteString<'static>) -> Result<String, Error> {
+            String::from_utf8(s.to_vec()).map_err(|_| Error::NotUtf8)
+        }
+    }
+
+    impl<'de> de::Deserializer<'de> for Deserializer {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Int(i) => visitor.visit_i32(i),
+                Value::Int64(i) => visitor.visit_i64(i),
+                Value::Bool(b) => visitor.visit_bool(b),
+                Value::String(s) => visitor.visit_string(Deserializer::from_str(s)?),
+                Value::Double(d) => visitor.visit_f64(d),
+                Value::DateTime(date_time) => visitor.visit_string(::utils::format_datetime(&date_time)),
+                Value::Base64(b) => visitor.visit_byte_buf(b.to_vec()),
+                Value::Struct(members) => visitor.visit_map(StructAccess::new(members.into_owned())),
+                Value::Array(elements) => visitor.visit_seq(SeqAccess { elements: elements.into_owned().into_iter() }),
+                Value::Nil => visitor.visit_unit(),
+            }
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Nil => visitor.visit_none(),
+                other => visitor.visit_some(Deserializer { value: other }),
+            }
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Error> {
+            if name == DATETIME_NEWTYPE_NAME {
+                if let Value::DateTime(date_time) = self.value {
+                    return visitor.visit_newtype_struct(Deserializer {
+                        value: Value::from(::utils::format_datetime(&date_time)),
+                    });
+                }
+            }
+
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Struct(members) => visitor.visit_map(StructAccess::new(members.into_owned())),
+                _ => Err(Error::UnexpectedValue { expected: "a struct" }),
+            }
+        }
+
+        fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Struct(members) => visitor.visit_map(StructAccess::new(members.into_owned())),
+                _ => Err(Error::UnexpectedValue { expected: "a struct" }),
+            }
+        }
+
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Array(elements) => visitor.visit_seq(SeqAccess { elements: elements.into_owned().into_iter() }),
+                _ => Err(Error::UnexpectedValue { expected: "an array" }),
+            }
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::String(s) => visitor.visit_enum(de::value::StringDeserializer::new(Deserializer::from_str(s)?)),
+                Value::Struct(members) => {
+                    let mut members = members.into_owned();
+                    if members.len() != 1 {
+                        return Err(Error::UnexpectedValue { expected: "a single-member struct" });
+                    }
+                    let (variant, value) = members.remove(0);
+                    visitor.visit_enum(EnumAccess { variant: variant.into_owned(), value })
+                }
+                _ => Err(Error::UnexpectedValue { expected: "an enum" }),
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+            bytes byte_buf unit unit_struct tuple tuple_struct
+            identifier ignored_any
+        }
+    }
+
+    struct SeqAccess {
+        elements: ::std::vec::IntoIter<Value<'static>>,
+    }
+
+    impl<'de> de::SeqAccess<'de> for SeqAccess {
+        type Error = Error;
+
+        fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+            match self.elements.next() {
+                Some(value) => seed.deserialize(Deserializer { value }).map(Some),
+                None => Ok(None),
+            }
+        }
+    }
+
+    struct StructAccess {
+        members: ::std::vec::IntoIter<(Cow<'static, str>, Value<'static>)>,
+        current: Option<Value<'static>>,
+    }
+
+    impl StructAccess {
+        /// Builds a `MapAccess` over `members`, keeping only the first occurrence of each name.
+        ///
+        /// Per the [spec][dup], `<struct>` member names aren't guaranteed unique; `Value::get`
+        /// resolves that by returning the first match, so this does the same rather than letting
+        /// duplicate keys reach `serde`'s derived `Visitor`s, which reject them outright.
+        ///
+        /// [dup]: http://xml-rpc.yahoogroups.narkive.com/Br9xMUtQ/duplicate-struct-member-names-allowed
+        fn new(members: Vec<(Cow<'static, str>, Value<'static>)>) -> Self {
+            let mut seen = ::std::collections::HashSet::new();
+            let members: Vec<_> = members.into_iter().filter(|(name, _)| seen.insert(name.clone())).collect();
+
+            StructAccess { members: members.into_iter(), current: None }
+        }
+    }
+
+    impl<'de> de::MapAccess<'de> for StructAccess {
+        type Error = Error;
+
+        fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+            match self.members.next() {
+                Some((name, value)) => {
+                    self.current = Some(value);
+                    seed.deserialize(Deserializer { value: Value::from(name.into_owned()) }).map(Some)
+                }
+                None => Ok(None),
+            }
+        }
+
+        fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+            let value = self.current.take().expect("next_value_seed called before next_key_seed");
+            seed.deserialize(Deserializer { value })
+        }
+    }
+
+    struct EnumAccess {
+        variant: String,
+        value: Value<'static>,
+    }
+
+    impl<'de> de::EnumAccess<'de> for EnumAccess {
+        type Error = Error;
+        type Variant = Deserializer;
+
+        fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Error> {
+            let variant = seed.deserialize(Deserializer { value: Value::from(self.variant) })?;
+            Ok((variant, Deserializer { value: self.value }))
+        }
+    }
+
+    impl<'de> de::VariantAccess<'de> for Deserializer {
+        type Error = Error;
+
+        fn unit_variant(self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+            seed.deserialize(self)
+        }
+
+        fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+            de::Deserializer::deserialize_seq(self, visitor)
+        }
+
+        fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+            de::Deserializer::deserialize_struct(self, "", fields, visitor)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use self::serde_support::{from_value, to_value, Error, Deserializer, Serializer};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,7 +1292,144 @@ mod tests {
         let mut map: BTreeMap<String, Value> = BTreeMap::new();
         map.insert("x&<x".to_string(), Value::from(true));
 
-        Value::Struct(map).format(&mut output).unwrap();
+        Value::from(map).format(&mut output).unwrap();
         assert_eq!(str::from_utf8(&output).unwrap(), "<value>\n<struct>\n<member>\n<name>x&amp;&lt;x</name>\n<value>\n<boolean>1</boolean>\n</value>\n</member>\n</struct>\n</value>\n");
     }
+
+    #[test]
+    fn accesses_typed_values() {
+        assert_eq!(Value::from(42).as_i32(), Some(42));
+        assert_eq!(Value::Int64(42).as_i64(), Some(42));
+        assert_eq!(Value::from(true).as_bool(), Some(true));
+        assert_eq!(Value::from(1.5).as_str(), None);
+        assert_eq!(Value::from("hi").as_str(), Some("hi"));
+    }
+
+    #[test]
+    fn indexes_into_struct_and_array() {
+        let mut map: BTreeMap<String, Value> = BTreeMap::new();
+        map.insert("numbers".to_string(), Value::from(vec![Value::from(1), Value::from(2)]));
+
+        let value = Value::from(map);
+        assert_eq!(value["numbers"][1].as_i32(), Some(2));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn parses_primitive_values() {
+        assert_eq!(Value::parse_str("<value><i4>42</i4></value>").unwrap(), Value::from(42));
+        assert_eq!(Value::parse_str("<value><boolean>1</boolean></value>").unwrap(), Value::from(true));
+        assert_eq!(Value::parse_str("<value>plain text</value>").unwrap(), Value::from("plain text"));
+        assert_eq!(Value::parse_str("<value><nil/></value>").unwrap(), Value::Nil);
+    }
+
+    #[test]
+    fn parses_struct_and_array() {
+        let value = Value::parse_str(
+            "<value><struct><member><name>n</name><value><array><data><value><i4>1</i4></value></data></array></value></member></struct></value>",
+        ).unwrap();
+        assert_eq!(value["n"][0].as_i32(), Some(1));
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn shares_bytes_without_copying() {
+        let data = ::bytes::Bytes::from(&b"hello"[..]);
+        let value = Value::bytes_from(data.clone());
+        assert_eq!(value.as_bytes(), Some(&b"hello"[..]));
+
+        if let Value::String(ByteString::Shared(shared)) = value {
+            assert_eq!(shared.as_ptr(), data.as_ptr());
+        } else {
+            panic!("expected a Shared ByteString");
+        }
+    }
+
+    #[test]
+    fn picks_string_or_base64_based_on_content() {
+        assert_eq!(Value::from_bytes(&b"plain text"[..]).as_str(), Some("plain text"));
+        assert_eq!(Value::from_bytes(&b"\x00\x01\x02"[..]).as_str(), None);
+        assert_eq!(Value::from_bytes(&b"\x00\x01\x02"[..]).as_bytes(), Some(&b"\x00\x01\x02"[..]));
+    }
+
+    #[test]
+    fn rejects_unrepresentable_string_content_on_format() {
+        let mut output: Vec<u8> = Vec::new();
+        assert!(Value::from(vec![0u8]).format(&mut output).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(feature = "serde")]
+    use super::serde_support::DATETIME_NEWTYPE_NAME;
+
+    #[cfg(feature = "serde")]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Circle,
+        Square,
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_a_struct() {
+        let value = to_value(Point { x: 1, y: 2 }).unwrap();
+        assert_eq!(from_value::<Point>(value).unwrap(), Point { x: 1, y: 2 });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_a_unit_enum() {
+        let value = to_value(Shape::Square).unwrap();
+        assert_eq!(value, Value::from("Square"));
+        assert_eq!(from_value::<Shape>(value).unwrap(), Shape::Square);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_an_option() {
+        assert_eq!(from_value::<Option<i32>>(to_value(Some(5)).unwrap()).unwrap(), Some(5));
+        assert_eq!(from_value::<Option<i32>>(to_value(None::<i32>).unwrap()).unwrap(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_the_datetime_newtype_pattern_as_a_datetime() {
+        struct XmlRpcDateTime(String);
+
+        impl ::serde::Serialize for XmlRpcDateTime {
+            fn serialize<S: ::serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_newtype_struct(DATETIME_NEWTYPE_NAME, &self.0)
+            }
+        }
+
+        let value = to_value(XmlRpcDateTime("2020-01-01T00:00:00Z".to_string())).unwrap();
+        assert_eq!(value, Value::DateTime(::iso8601::datetime("2020-01-01T00:00:00Z").unwrap()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rejects_out_of_range_integers() {
+        assert_eq!(to_value(u64::max_value()), Err(Error::IntegerOutOfRange));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deduplicates_struct_members_by_first_occurrence_when_deserializing() {
+        let value = Value::Struct(Cow::Owned(vec![
+            (Cow::Borrowed("x"), Value::from(10)),
+            (Cow::Borrowed("x"), Value::from(20)),
+            (Cow::Borrowed("y"), Value::from(3)),
+        ]));
+
+        assert_eq!(from_value::<Point>(value).unwrap(), Point { x: 10, y: 3 });
+    }
 }